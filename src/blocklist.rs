@@ -8,14 +8,39 @@ Filtering methods can be used on [Url]s.
 use std::{
     collections::HashSet,
     fs::File,
-    io::{BufRead, BufReader},
+    io::{BufRead, BufReader, BufWriter},
+    net::IpAddr,
     path::{Path, PathBuf},
+    str::FromStr,
 };
 
-use url::{Position, Url};
+use ipnet::IpNet;
+use rayon::prelude::*;
+use regex::RegexSet;
+use url::{Host, Position, Url};
 
 use crate::error::Ut1Error as Error;
 
+/// Storage backend for a [`Blocklist`]'s domain/URL set.
+///
+/// `HashSet` is fast to build and query but keeps every entry resident in memory.
+/// `Fst` memory-maps a sorted, deduplicated finite-state transducer built once from the
+/// UT1 text file, trading a little query-time overhead for near-zero resident memory on
+/// huge categories (see [`Blocklist::with_folder_fst`]).
+enum Backend {
+    HashSet(HashSet<String>),
+    Fst(fst::Set<memmap2::Mmap>),
+}
+
+impl Backend {
+    fn contains(&self, key: &str) -> bool {
+        match self {
+            Backend::HashSet(set) => set.contains(key),
+            Backend::Fst(set) => set.contains(key),
+        }
+    }
+}
+
 /// Blocklist instantiation/detection.
 ///
 ///  A Blocklist contains a `kind` which corresponds to a folder name,
@@ -26,24 +51,67 @@ use crate::error::Ut1Error as Error;
 ///
 pub struct Blocklist<'a> {
     kind: &'a str,
-    domains: HashSet<String>,
-    urls: HashSet<String>,
+    domains: Backend,
+    urls: Backend,
+    /// IP addresses/CIDR blocks parsed out of the `domains` entries, checked
+    /// against `url.host()` when it resolves to an IP literal (see
+    /// [`detect_domain`](Self::detect_domain)).
+    ip_networks: Vec<IpNet>,
+    /// Patterns compiled from the `expressions`/`very_restrictive_expression` files.
+    expressions: RegexSet,
+    /// When `true`, [`detect_domain`](Self::detect_domain) walks subdomains
+    /// instead of comparing the host exactly.
+    recursive_subdomains: bool,
+}
+
+/// Parse every entry that's a bare IP address or a CIDR block into an [`IpNet`],
+/// so `detect_domain` can match against it when a URL's host is an IP literal.
+/// A bare address (`192.168.1.5`) becomes a single-address network (`/32`/`/128`).
+fn parse_ip_networks<'e>(entries: impl IntoIterator<Item = &'e String>) -> Vec<IpNet> {
+    entries
+        .into_iter()
+        .filter_map(|entry| {
+            IpNet::from_str(entry)
+                .ok()
+                .or_else(|| IpAddr::from_str(entry).ok().map(IpNet::from))
+        })
+        .collect()
 }
 
 impl<'a> Blocklist<'a> {
     /// Create a new Blocklist of provided kind.
     ///
-    pub fn new(kind: &'a str, domains: HashSet<String>, urls: HashSet<String>) -> Self {
+    pub fn new(
+        kind: &'a str,
+        domains: HashSet<String>,
+        urls: HashSet<String>,
+        expressions: RegexSet,
+    ) -> Self {
+        let ip_networks = parse_ip_networks(&domains);
+
         Self {
             kind,
-            domains,
-            urls,
+            domains: Backend::HashSet(domains),
+            urls: Backend::HashSet(urls),
+            ip_networks,
+            expressions,
+            recursive_subdomains: false,
         }
     }
 
+    /// When `enabled`, [`detect_domain`](Self::detect_domain) matches `foo.bar` against
+    /// `www.foo.bar`, `ads.cdn.foo.bar`, etc., stopping at the public-suffix boundary
+    /// (see [`detect_domain_recursive`](Self::detect_domain_recursive)).
+    pub fn recursive_subdomains(mut self, enabled: bool) -> Self {
+        self.recursive_subdomains = enabled;
+        self
+    }
+
     /// create a blocklist from specified kind and folder.
     ///
-    /// It will look for  `path/of/the/folder/kind`.
+    /// It will look for  `path/of/the/folder/kind`. `domains` must exist, but
+    /// `urls` is optional (some UT1 categories ship only a `domains` file) and
+    /// is treated as empty when absent.
     pub fn with_folder(kind: &'a str, folder: &Path) -> Result<Self, Error> {
         let mut file_path = PathBuf::from(folder);
 
@@ -60,22 +128,46 @@ impl<'a> Blocklist<'a> {
 
         let domains =
             File::open(domains).map_err(|_| Error::BlocklistNotFound(file_path.clone()))?;
-        let urls = File::open(urls).map_err(|_| Error::BlocklistNotFound(file_path.clone()))?;
 
-        let domains = BufReader::new(domains)
-            .lines()
-            .filter_map(Result::ok)
-            .collect();
-
-        let urls = BufReader::new(urls)
+        let domains: HashSet<String> = BufReader::new(domains)
             .lines()
             .filter_map(Result::ok)
             .collect();
+        let ip_networks = parse_ip_networks(&domains);
+
+        // `urls` is optional: plenty of real UT1 categories (e.g. `arjel`,
+        // `associations_religieuses`) ship only a `domains` file.
+        let urls: HashSet<String> = File::open(urls)
+            .map(|file| BufReader::new(file).lines().filter_map(Result::ok).collect())
+            .unwrap_or_default();
+
+        let mut expression_patterns = Vec::new();
+        for filename in ["expressions", "very_restrictive_expression"] {
+            let expressions_path = file_path.join(filename);
+            if !expressions_path.exists() {
+                continue;
+            }
+
+            if let Ok(file) = File::open(&expressions_path) {
+                // skip invalid patterns rather than aborting the build,
+                // mirroring how malformed domains/urls are dropped above
+                expression_patterns.extend(
+                    BufReader::new(file)
+                        .lines()
+                        .filter_map(Result::ok)
+                        .filter(|pattern| regex::Regex::new(pattern).is_ok()),
+                );
+            }
+        }
+        let expressions = RegexSet::new(&expression_patterns).unwrap_or_else(|_| RegexSet::empty());
 
         Ok(Self {
             kind,
-            domains,
-            urls,
+            domains: Backend::HashSet(domains),
+            urls: Backend::HashSet(urls),
+            ip_networks,
+            expressions,
+            recursive_subdomains: false,
         })
     }
 
@@ -85,6 +177,106 @@ impl<'a> Blocklist<'a> {
         Self::with_folder("adult", &default_folder)
     }
 
+    /// Create a [Blocklist] whose domain/URL sets are backed by memory-mapped FSTs
+    /// instead of in-memory `HashSet`s, for UT1 categories with millions of entries.
+    ///
+    /// Builds `domains.fst`/`urls.fst` sidecar files next to the UT1 `domains`/`urls`
+    /// text files the first time they're needed, then just memory-maps them on
+    /// subsequent calls. The public API (`detect_domain`/`detect_url`/...) is unchanged.
+    pub fn with_folder_fst(kind: &'a str, folder: &Path) -> Result<Self, Error> {
+        let mut file_path = PathBuf::from(folder);
+
+        if !file_path.is_dir() {
+            return Err(Error::NotADirectory(file_path));
+        }
+
+        file_path.push(kind);
+
+        let domains_path = file_path.join("domains");
+        if !domains_path.is_file() {
+            return Err(Error::BlocklistNotFound(file_path));
+        }
+
+        let ip_networks = File::open(&domains_path)
+            .map(|file| {
+                let entries: Vec<String> = BufReader::new(file).lines().filter_map(Result::ok).collect();
+                parse_ip_networks(&entries)
+            })
+            .unwrap_or_default();
+
+        let domains = Self::fst_backend(&domains_path, &file_path.join("domains.fst"))?;
+        // `urls` is optional, same as in `with_folder`: categories like `arjel`
+        // ship only a `domains` file, so build an empty sidecar for it.
+        let urls = Self::fst_backend(&file_path.join("urls"), &file_path.join("urls.fst"))?;
+
+        let mut expression_patterns = Vec::new();
+        for filename in ["expressions", "very_restrictive_expression"] {
+            let expressions_path = file_path.join(filename);
+            if !expressions_path.exists() {
+                continue;
+            }
+
+            if let Ok(file) = File::open(&expressions_path) {
+                expression_patterns.extend(
+                    BufReader::new(file)
+                        .lines()
+                        .filter_map(Result::ok)
+                        .filter(|pattern| regex::Regex::new(pattern).is_ok()),
+                );
+            }
+        }
+        let expressions = RegexSet::new(&expression_patterns).unwrap_or_else(|_| RegexSet::empty());
+
+        Ok(Self {
+            kind,
+            domains,
+            urls,
+            ip_networks,
+            expressions,
+            recursive_subdomains: false,
+        })
+    }
+
+    /// Build (if missing) and memory-map the FST sidecar for `text_path`, whose
+    /// lines become the set's keys.
+    ///
+    /// A missing `text_path` builds an empty sidecar rather than erroring, since
+    /// some UT1 categories ship only a `domains` file and no `urls`.
+    fn fst_backend(text_path: &Path, fst_path: &Path) -> Result<Backend, Error> {
+        if !fst_path.exists() {
+            let mut keys: Vec<String> = File::open(text_path)
+                .map(|file| BufReader::new(file).lines().filter_map(Result::ok).collect())
+                .unwrap_or_default();
+            keys.sort_unstable();
+            keys.dedup();
+
+            let writer = BufWriter::new(
+                File::create(fst_path)
+                    .map_err(|e| Error::Fst(fst_path.to_path_buf(), e.to_string()))?,
+            );
+            let mut builder = fst::SetBuilder::new(writer)
+                .map_err(|e| Error::Fst(fst_path.to_path_buf(), e.to_string()))?;
+            for key in &keys {
+                builder
+                    .insert(key)
+                    .map_err(|e| Error::Fst(fst_path.to_path_buf(), e.to_string()))?;
+            }
+            builder
+                .finish()
+                .map_err(|e| Error::Fst(fst_path.to_path_buf(), e.to_string()))?;
+        }
+
+        let file = File::open(fst_path).map_err(|e| Error::Fst(fst_path.to_path_buf(), e.to_string()))?;
+        // SAFETY: the sidecar is only ever written by `fst_backend` itself and is not
+        // expected to be concurrently modified by another process while mapped.
+        let mmap = unsafe { memmap2::Mmap::map(&file) }
+            .map_err(|e| Error::Fst(fst_path.to_path_buf(), e.to_string()))?;
+        let set =
+            fst::Set::new(mmap).map_err(|e| Error::Fst(fst_path.to_path_buf(), e.to_string()))?;
+
+        Ok(Backend::Fst(set))
+    }
+
     /// Get a reference to the blocklist's domains.
     // pub fn domains(&self) -> &HashSet<String> {
     //     &self.domains
@@ -93,12 +285,46 @@ impl<'a> Blocklist<'a> {
     /// returns `true` if domain of the provided url is in the domains list,
     /// `false` if not, or if there's no domain in the url.
     ///
+    /// Delegates to [`detect_domain_recursive`](Self::detect_domain_recursive) when
+    /// `recursive_subdomains` is enabled (see [`Self::recursive_subdomains`]).
     pub fn detect_domain(&self, url: &Url) -> bool {
-        if let Some(domain) = url.host_str() {
-            self.domains.contains(domain)
-        } else {
-            false
+        if self.recursive_subdomains {
+            return self.detect_domain_recursive(url);
+        }
+
+        match url.host() {
+            Some(Host::Ipv4(ip)) => self.ip_networks.iter().any(|net| net.contains(&IpAddr::V4(ip))),
+            Some(Host::Ipv6(ip)) => self.ip_networks.iter().any(|net| net.contains(&IpAddr::V6(ip))),
+            Some(Host::Domain(domain)) => self.domains.contains(domain),
+            None => false,
+        }
+    }
+
+    /// returns `true` if the domain of the provided url, or one of its parent domains,
+    /// is in the domains list, e.g. a `foo.bar` entry also matches `www.foo.bar` and
+    /// `ads.cdn.foo.bar`.
+    ///
+    /// Never climbs to an ICANN public-suffix boundary (e.g. `co.uk`), so a bare
+    /// `co.uk` entry never matches `evil.co.uk`; PSL private-section suffixes
+    /// (e.g. `blogspot.com`, `github.io`) are still checked as parents. IP-literal
+    /// hosts are checked against the IP/CIDR entries and are never walked.
+    pub fn detect_domain_recursive(&self, url: &Url) -> bool {
+        let domain = match url.host() {
+            Some(Host::Domain(domain)) => domain,
+            Some(Host::Ipv4(ip)) => {
+                return self.ip_networks.iter().any(|net| net.contains(&IpAddr::V4(ip)))
+            }
+            Some(Host::Ipv6(ip)) => {
+                return self.ip_networks.iter().any(|net| net.contains(&IpAddr::V6(ip)))
+            }
+            None => return false,
+        };
+
+        if self.domains.contains(domain) {
+            return true;
         }
+
+        crate::domain_suffix::registrable_parents(domain).any(|parent| self.domains.contains(parent))
     }
 
     /// returns `true` if url is in the domains list.
@@ -111,6 +337,23 @@ impl<'a> Blocklist<'a> {
         self.urls.contains(url)
     }
 
+    /// returns `true` if url matches one of the patterns loaded from the
+    /// `expressions`/`very_restrictive_expression` files.
+    pub fn detect_expression(&self, url: &Url) -> bool {
+        self.expressions.is_match(url.as_str())
+    }
+
+    /// Classify a batch of URLs in parallel, returning each URL's verdict
+    /// (domain, URL, or expression match) in input order.
+    ///
+    /// Prefer this over calling [`detect_domain`](Self::detect_domain)/[`detect_url`](Self::detect_url)/
+    /// [`detect_expression`](Self::detect_expression) in a loop when classifying large streams.
+    pub fn detect_batch(&self, urls: &[Url]) -> Vec<bool> {
+        urls.par_iter()
+            .map(|url| self.detect_domain(url) || self.detect_url(url) || self.detect_expression(url))
+            .collect()
+    }
+
     /// Get a reference to the blocklist's kind.
     pub fn kind(&self) -> &'a str {
         &self.kind
@@ -141,6 +384,7 @@ impl<'a> Blocklist<'a> {
 mod tests {
     use std::{collections::HashSet, error::Error, fs::File, io::Write, str::FromStr};
 
+    use regex::RegexSet;
     use url::Url;
 
     use super::Blocklist;
@@ -166,7 +410,7 @@ mod tests {
     #[test]
     fn test_new() {
         let domains = vec!["foo.bar".to_string()].into_iter();
-        let bl = Blocklist::new("test", domains.collect(), HashSet::new());
+        let bl = Blocklist::new("test", domains.collect(), HashSet::new(), RegexSet::empty());
 
         let is_detected = Url::from_str("https://foo.bar").unwrap();
         let is_not_detected = Url::from_str("https://baz.quux").unwrap();
@@ -220,4 +464,155 @@ mod tests {
 
         assert!(!bl.detect_url(&url));
     }
+
+    #[test]
+    fn detect_domain_recursive_matches_subdomains() {
+        let bl = get_test_blocklist().unwrap().recursive_subdomains(true);
+
+        assert!(bl.detect_domain(&Url::from_str("https://www.foo.bar").unwrap()));
+        assert!(bl.detect_domain(&Url::from_str("https://ads.cdn.foo.bar").unwrap()));
+        assert!(!bl.detect_domain(&Url::from_str("https://foobar.baz").unwrap()));
+    }
+
+    #[test]
+    fn detect_domain_recursive_excludes_bare_public_suffix() {
+        let domains = HashSet::from(["co.uk".to_string()]);
+        let bl = Blocklist::new("test", domains, HashSet::new(), RegexSet::empty())
+            .recursive_subdomains(true);
+
+        // a bare `co.uk` entry must never match through a parent that is itself
+        // the public suffix boundary.
+        assert!(!bl.detect_domain(&Url::from_str("https://evil.co.uk").unwrap()));
+    }
+
+    #[test]
+    fn detect_domain_recursive_matches_private_psl_suffix() {
+        // `blogspot.com` is itself a recognized PSL *private*-section suffix,
+        // but UT1's hosting-platform categories list it directly, so climbing
+        // must still match it.
+        let domains = HashSet::from(["blogspot.com".to_string()]);
+        let bl = Blocklist::new("test", domains, HashSet::new(), RegexSet::empty())
+            .recursive_subdomains(true);
+
+        assert!(bl.detect_domain(&Url::from_str("https://foo.blogspot.com").unwrap()));
+    }
+
+    #[test]
+    fn detect_domain_recursive_stops_at_ip() {
+        let bl = get_test_blocklist().unwrap().recursive_subdomains(true);
+
+        assert!(!bl.detect_domain(&Url::from_str("https://127.0.0.1").unwrap()));
+    }
+
+    #[test]
+    fn domain_matches_bare_address_entry() {
+        let domains = HashSet::from(["192.168.1.5".to_string()]);
+        let bl = Blocklist::new("test", domains, HashSet::new(), RegexSet::empty());
+
+        assert!(bl.detect_domain(&Url::from_str("https://192.168.1.5").unwrap()));
+        assert!(!bl.detect_domain(&Url::from_str("https://192.168.1.6").unwrap()));
+    }
+
+    #[test]
+    fn domain_matches_cidr_block_entry() {
+        let domains = HashSet::from(["192.168.1.0/24".to_string()]);
+        let bl = Blocklist::new("test", domains, HashSet::new(), RegexSet::empty());
+
+        assert!(bl.detect_domain(&Url::from_str("https://192.168.1.42").unwrap()));
+        assert!(!bl.detect_domain(&Url::from_str("https://192.168.2.42").unwrap()));
+    }
+
+    #[test]
+    fn detect_batch_matches_per_url() {
+        let bl = get_test_blocklist().unwrap();
+        let urls = vec![
+            Url::from_str("https://foo.bar").unwrap(),
+            Url::from_str("https://good.domain").unwrap(),
+            Url::from_str("https://foo.bar/baz").unwrap(),
+        ];
+
+        assert_eq!(bl.detect_batch(&urls), vec![true, false, true]);
+    }
+
+    #[test]
+    fn detect_expression_matches_pattern_and_skips_invalid() {
+        let bl_folder = tempfile::tempdir().unwrap();
+        let bl_adult_folder = bl_folder.path().join("adult");
+        std::fs::create_dir(&bl_adult_folder).unwrap();
+
+        File::create(bl_adult_folder.join("domains")).unwrap();
+        File::create(bl_adult_folder.join("urls")).unwrap();
+        File::create(bl_adult_folder.join("expressions"))
+            .unwrap()
+            .write_all(b"/wp-admin\n[invalid(regex\n")
+            .unwrap();
+
+        let bl = Blocklist::with_folder("adult", bl_folder.path()).unwrap();
+
+        assert!(bl.detect_expression(&Url::from_str("https://foo.bar/wp-admin/login").unwrap()));
+        assert!(!bl.detect_expression(&Url::from_str("https://foo.bar/safe").unwrap()));
+    }
+
+    #[test]
+    fn with_folder_tolerates_missing_urls_file() {
+        let bl_folder = tempfile::tempdir().unwrap();
+        let bl_adult_folder = bl_folder.path().join("arjel");
+        std::fs::create_dir(&bl_adult_folder).unwrap();
+
+        File::create(bl_adult_folder.join("domains"))
+            .unwrap()
+            .write_all("foo.bar".as_bytes())
+            .unwrap();
+
+        let bl = Blocklist::with_folder("arjel", bl_folder.path()).unwrap();
+
+        assert!(bl.detect_domain(&Url::from_str("https://foo.bar").unwrap()));
+        assert!(!bl.detect_url(&Url::from_str("https://foo.bar/baz").unwrap()));
+    }
+
+    #[test]
+    fn fst_backend_matches_same_as_hashset() {
+        let bl_folder = tempfile::tempdir().unwrap();
+        let bl_adult_folder = bl_folder.path().join("adult");
+        std::fs::create_dir(&bl_adult_folder).unwrap();
+
+        File::create(bl_adult_folder.join("domains"))
+            .unwrap()
+            .write_all("foo.bar".as_bytes())
+            .unwrap();
+        File::create(bl_adult_folder.join("urls"))
+            .unwrap()
+            .write_all("foo.bar/baz".as_bytes())
+            .unwrap();
+
+        let bl = Blocklist::with_folder_fst("adult", bl_folder.path()).unwrap();
+
+        assert!(bl.detect_domain(&Url::from_str("https://foo.bar").unwrap()));
+        assert!(!bl.detect_domain(&Url::from_str("https://good.domain").unwrap()));
+        assert!(bl.detect_url(&Url::from_str("https://foo.bar/baz").unwrap()));
+
+        // the sidecar .fst file must have been written next to the text files
+        assert!(bl_adult_folder.join("domains.fst").exists());
+
+        // calling it again reuses the sidecar instead of rebuilding it
+        let bl = Blocklist::with_folder_fst("adult", bl_folder.path()).unwrap();
+        assert!(bl.detect_domain(&Url::from_str("https://foo.bar").unwrap()));
+    }
+
+    #[test]
+    fn with_folder_fst_tolerates_missing_urls_file() {
+        let bl_folder = tempfile::tempdir().unwrap();
+        let bl_arjel_folder = bl_folder.path().join("arjel");
+        std::fs::create_dir(&bl_arjel_folder).unwrap();
+
+        File::create(bl_arjel_folder.join("domains"))
+            .unwrap()
+            .write_all("foo.bar".as_bytes())
+            .unwrap();
+
+        let bl = Blocklist::with_folder_fst("arjel", bl_folder.path()).unwrap();
+
+        assert!(bl.detect_domain(&Url::from_str("https://foo.bar").unwrap()));
+        assert!(!bl.detect_url(&Url::from_str("https://foo.bar/baz").unwrap()));
+    }
 }
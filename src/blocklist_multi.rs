@@ -45,26 +45,209 @@
 use std::{
     collections::{HashMap, HashSet},
     fs::File,
+    hash::Hash,
     io::{BufRead, BufReader},
     path::Path,
+    rc::Rc,
 };
 
 use crate::error::Ut1Error;
-use log::{debug, info};
+use log::{debug, info, warn};
+use regex::RegexSet;
+use smallvec::SmallVec;
 use url::{Position, Url};
 
-// TODO: replace owned strings by refs to a (static?) tag.
+/// A small set of interned category-tag ids. Most domains/URLs belong to a
+/// handful of categories, so this stays inline for the common case.
+type TagIds = SmallVec<[u32; 4]>;
+/// Shared, deduplicated tag-id set: domains/URLs carrying an identical set of
+/// tags point at the same allocation instead of each holding their own copy.
+type TagSet = Rc<TagIds>;
+
 /// Domain and URL blocklist
 #[derive(Clone)]
 pub struct Blocklist {
-    domains: HashMap<String, Vec<String>>,
-    urls: HashMap<Url, Vec<String>>,
+    /// Unique category names, indexed by tag id.
+    tags: Vec<String>,
+    domains: HashMap<String, TagSet>,
+    urls: HashMap<Url, TagSet>,
+    /// Patterns compiled from the `expressions`/`very_restrictive_expression` files.
+    expressions: RegexSet,
+    /// Tag id for each pattern in `expressions`, indexed the same way.
+    expression_tags: Vec<u32>,
+    /// Domains that must never be reported, even if present in `domains`.
+    allow: HashSet<String>,
+    /// URLs that must never be reported, even if present in `urls`.
+    allow_urls: HashSet<String>,
+    /// When `true`, the allowlist semantics invert: anything *not* in `allow`/`allow_urls`
+    /// is flagged, and entries that match are never flagged.
+    allow_only: bool,
+    /// Label returned by [`detect`](Self::detect) for hits produced by `allow_only` mode.
+    /// Kept separate from `tags` so this synthetic entry never counts towards
+    /// [`stats`](Self::stats)'s `category_count`.
+    allow_only_label: String,
+    /// Largest number of dot-separated labels among all `domains` keys,
+    /// so [`detect_subdomains`](Self::detect_subdomains) can skip candidates
+    /// that are longer than anything a loaded category could contain.
+    max_subdomain_depth: usize,
+}
+
+/// Summary statistics about a built [`Blocklist`], mostly useful for sizing/monitoring.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    /// Number of distinct domains carrying at least one tag.
+    pub domain_entries: usize,
+    /// Number of distinct URLs carrying at least one tag.
+    pub url_entries: usize,
+    /// Number of distinct category tags known to this blocklist.
+    pub category_count: usize,
+    /// Largest number of dot-separated labels among all domain entries.
+    pub max_subdomain_depth: usize,
 }
 
 impl Blocklist {
-    pub fn new(domains: HashMap<String, Vec<String>>, urls: HashMap<Url, Vec<String>>) -> Self {
-        Self { domains, urls }
+    pub fn new(
+        domains: HashMap<String, Vec<String>>,
+        urls: HashMap<Url, Vec<String>>,
+        expressions: RegexSet,
+        expression_tags: Vec<String>,
+    ) -> Self {
+        let mut tags: Vec<String> = Vec::new();
+        let mut tag_ids: HashMap<String, u32> = HashMap::new();
+
+        let domains: HashMap<String, TagIds> = domains
+            .into_iter()
+            .map(|(domain, names)| {
+                let ids = names
+                    .iter()
+                    .map(|name| Self::intern(&mut tags, &mut tag_ids, name))
+                    .collect();
+                (domain, ids)
+            })
+            .collect();
+
+        let urls: HashMap<Url, TagIds> = urls
+            .into_iter()
+            .map(|(url, names)| {
+                let ids = names
+                    .iter()
+                    .map(|name| Self::intern(&mut tags, &mut tag_ids, name))
+                    .collect();
+                (url, ids)
+            })
+            .collect();
+
+        let expression_tags = expression_tags
+            .iter()
+            .map(|name| Self::intern(&mut tags, &mut tag_ids, name))
+            .collect();
+
+        let max_subdomain_depth = Self::max_subdomain_depth(domains.keys());
+        let domains = Self::fuse_tagsets(domains);
+        let urls = Self::fuse_tagsets(urls);
+
+        Self {
+            tags,
+            domains,
+            urls,
+            expressions,
+            expression_tags,
+            allow: HashSet::new(),
+            allow_urls: HashSet::new(),
+            allow_only: false,
+            allow_only_label: "not-allowlisted".to_string(),
+            max_subdomain_depth,
+        }
+    }
+
+    /// Add an allowlist: any domain in `domains` or URL in `urls` will never be
+    /// reported by [`detect`](Self::detect), even if it also appears in a loaded category.
+    pub fn with_allowlist(
+        mut self,
+        domains: impl IntoIterator<Item = String>,
+        urls: impl IntoIterator<Item = String>,
+    ) -> Self {
+        self.allow = domains.into_iter().collect();
+        // normalize like `from_dir` does for `urls` entries, so schemeless UT1-style
+        // entries (`foo.bar/baz`) match the normalized URL that `detect` checks
+        self.allow_urls = urls
+            .into_iter()
+            .filter_map(|url| Self::normalize_url(&url).ok())
+            .map(|url| url.to_string())
+            .collect();
+        self
+    }
+
+    /// Add an allowlist read from a directory containing `domains` and/or `urls` files,
+    /// using the same layout as a single UT1 category.
+    pub fn with_allowlist_dir(self, dir: &Path) -> Result<Self, std::io::Error> {
+        let read_lines = |path: std::path::PathBuf| -> Vec<String> {
+            File::open(path)
+                .map(|f| BufReader::new(f).lines().filter_map(Result::ok).collect())
+                .unwrap_or_default()
+        };
+
+        let domains = read_lines(dir.join("domains"));
+        let urls = read_lines(dir.join("urls"));
+
+        Ok(self.with_allowlist(domains, urls))
+    }
+
+    /// When `allow_only` is `true`, invert the allowlist semantics: treat the
+    /// loaded allow set as the *only* things allowed, and flag everything else.
+    pub fn allow_only(mut self, allow_only: bool) -> Self {
+        self.allow_only = allow_only;
+        self
+    }
+
+    /// Report counts and sizing information about this blocklist.
+    pub fn stats(&self) -> Stats {
+        Stats {
+            domain_entries: self.domains.len(),
+            url_entries: self.urls.len(),
+            category_count: self.tags.len(),
+            max_subdomain_depth: self.max_subdomain_depth,
+        }
     }
+
+    /// Get the tag id for `name`, interning it if it hasn't been seen before.
+    fn intern(tags: &mut Vec<String>, tag_ids: &mut HashMap<String, u32>, name: &str) -> u32 {
+        if let Some(&id) = tag_ids.get(name) {
+            return id;
+        }
+
+        let id = tags.len() as u32;
+        tags.push(name.to_string());
+        tag_ids.insert(name.to_string(), id);
+        id
+    }
+
+    /// Largest number of dot-separated labels among `domains`.
+    fn max_subdomain_depth<'a>(domains: impl Iterator<Item = &'a String>) -> usize {
+        domains
+            .map(|domain| domain.matches('.').count() + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Adblock-style fusing pass: entries carrying an identical tag-id set share
+    /// a single allocation instead of each holding their own `Vec`/`SmallVec`.
+    fn fuse_tagsets<K: Eq + Hash>(raw: HashMap<K, TagIds>) -> HashMap<K, TagSet> {
+        let mut pool: HashMap<TagIds, TagSet> = HashMap::new();
+
+        raw.into_iter()
+            .map(|(key, mut ids)| {
+                ids.sort_unstable();
+                ids.dedup();
+                let shared = pool
+                    .entry(ids.clone())
+                    .or_insert_with(|| Rc::new(ids))
+                    .clone();
+                (key, shared)
+            })
+            .collect()
+    }
+
     /// Try to build a [Url] from a string representing an URL.
     /// If it fails, tries again by adding https:// at the beginning.
     #[inline]
@@ -126,8 +309,12 @@ impl Blocklist {
     */
     pub fn from_dir(dir: &Path) -> Result<Self, std::io::Error> {
         info!("Building list from {dir:?}");
-        let mut domains: HashMap<_, Vec<_>> = HashMap::new();
-        let mut urls: HashMap<_, Vec<_>> = HashMap::new();
+        let mut tags: Vec<String> = Vec::new();
+        let mut tag_ids: HashMap<String, u32> = HashMap::new();
+        let mut domains: HashMap<String, TagIds> = HashMap::new();
+        let mut urls: HashMap<Url, TagIds> = HashMap::new();
+        let mut expression_patterns: Vec<String> = Vec::new();
+        let mut expression_tags: Vec<u32> = Vec::new();
 
         for blocklist_path in std::fs::read_dir(dir)? {
             let blocklist_path = blocklist_path?.path();
@@ -137,6 +324,7 @@ impl Blocklist {
                 .to_string_lossy()
                 .to_string();
             debug!("Reading lists for category {bl_name:?}");
+            let tag_id = Self::intern(&mut tags, &mut tag_ids, &bl_name);
 
             let domain_path = {
                 let mut d = blocklist_path.clone();
@@ -160,12 +348,7 @@ impl Blocklist {
                     .filter_map(|url| Self::normalize_domain(&url).ok());
 
                 for domain in bl_domains {
-                    // insert a new vec with blocklist name in it,
-                    // or push the name in the existing vec
-                    domains
-                        .entry(domain)
-                        .and_modify(|v| v.push(bl_name.clone()))
-                        .or_insert_with(|| vec![bl_name.clone()]);
+                    domains.entry(domain).or_default().push(tag_id);
                 }
             }
 
@@ -178,57 +361,77 @@ impl Blocklist {
                     .filter_map(|url| Self::normalize_url(&url).ok());
 
                 for url in bl_urls {
-                    // insert a new vec with blocklist name in it,
-                    // or push the name in the existing vec
-                    urls.entry(url)
-                        .and_modify(|v| v.push(bl_name.clone()))
-                        .or_insert_with(|| vec![bl_name.clone()]);
+                    urls.entry(url).or_default().push(tag_id);
+                }
+            }
+
+            for filename in ["expressions", "very_restrictive_expression"] {
+                let expressions_path = blocklist_path.join(filename);
+                if !expressions_path.exists() {
+                    continue;
+                }
+
+                debug!("loading {filename} for category {bl_name:?}");
+                let r = File::open(&expressions_path)?;
+
+                for pattern in BufReader::new(r).lines().filter_map(Result::ok) {
+                    // mirror how malformed domains/urls are dropped rather than
+                    // aborting the whole build on one bad pattern
+                    if regex::Regex::new(&pattern).is_ok() {
+                        expression_patterns.push(pattern);
+                        expression_tags.push(tag_id);
+                    } else {
+                        warn!("skipping invalid expression pattern in {bl_name:?}: {pattern:?}");
+                    }
                 }
             }
         }
 
-        Ok(Self { domains, urls })
+        let expressions = RegexSet::new(&expression_patterns).unwrap_or_else(|e| {
+            warn!("failed to compile expression patterns, disabling them: {e}");
+            RegexSet::empty()
+        });
+
+        let max_subdomain_depth = Self::max_subdomain_depth(domains.keys());
+        let domains = Self::fuse_tagsets(domains);
+        let urls = Self::fuse_tagsets(urls);
+
+        Ok(Self {
+            tags,
+            domains,
+            urls,
+            expressions,
+            expression_tags,
+            allow: HashSet::new(),
+            allow_urls: HashSet::new(),
+            allow_only: false,
+            allow_only_label: "not-allowlisted".to_string(),
+            max_subdomain_depth,
+        })
     }
 
-    /// iteratively removes subdomains until there's a match
-    // TODO optim: we know max number of subdomains in blocklist,
-    //             so we could skip more
-    fn detect_subdomains(&self, domain: &str) -> Option<HashSet<&String>> {
-        // keep domain as vector of chars since we'll rely heavily on indexing
-        // we use bytes to be able to use from_utf8 without having to allocate
-        // it should be safe because even if we have some non utf8 chars, . is utf8
-        let chars = domain.as_bytes();
-
-        // get char indexes of dots (in for example foo.bar.com)
-        let mut sep_positions: Vec<usize> = chars
-            .iter()
-            .enumerate()
-            .filter(|(_, c)| c == &&(b'.'))
-            .map(|(idx, _)| idx)
-            .collect();
-
-        // remove last position (between domain and TLD)
-        // so that we don't match on tld alone
-        sep_positions.pop();
+    /// `true` if `domain`, or one of its parent domains up to the registrable boundary,
+    /// is present in the allowlist.
+    fn is_domain_allowed(&self, domain: &str) -> bool {
+        if self.allow.contains(domain) {
+            return true;
+        }
 
-        // iterate over separator positions
-        let categories: HashSet<&String> = sep_positions
-            .into_iter()
-            .filter_map(|pos| {
-                // string to test is 1 char after the subdomain delimiter unil the end
-                // ignore if we can't build a string slice
-                if let Ok(to_test) = std::str::from_utf8(&chars[pos + 1..]) {
-                    if let Some(categories) = self.domains.get(to_test) {
-                        // return categories if there's a match
-                        return Some(categories);
-                    }
+        crate::domain_suffix::registrable_parents(domain).any(|parent| self.allow.contains(parent))
+    }
 
-                    return None;
-                }
-                None
-            })
-            // flatten nested vectors
-            .flatten()
+    /// iteratively removes subdomains until there's a match,
+    /// never climbing to an ICANN public-suffix boundary (e.g. `co.uk`), so that a bare
+    /// `co.uk` entry never matches; PSL private-section suffixes (e.g. `blogspot.com`)
+    /// are still checked.
+    fn detect_subdomains(&self, domain: &str) -> Option<HashSet<u32>> {
+        // iterate over parent domains, stopping at the public-suffix boundary
+        let categories: HashSet<u32> = crate::domain_suffix::registrable_parents(domain)
+            // no loaded entry has more labels than max_subdomain_depth, so skip
+            // candidates that still have more than that
+            .filter(|candidate| candidate.matches('.').count() + 1 <= self.max_subdomain_depth)
+            .filter_map(|candidate| self.domains.get(candidate))
+            .flat_map(|ids| ids.iter().copied())
             .collect();
 
         if categories.is_empty() {
@@ -242,32 +445,182 @@ impl Blocklist {
     /// If a given URL is present both in domain and urls, merges the tags.
     /// The returning hashset cannot be empty.
     pub fn detect(&self, url: &str) -> Option<HashSet<&String>> {
-        let mut detections = HashSet::new();
+        let mut tag_hits: HashSet<u32> = HashSet::new();
+        let mut allowed = false;
 
         if let Ok(domain) = Self::normalize_domain(url) {
+            allowed |= self.is_domain_allowed(&domain);
+
             // try with full domain
-            let domain_tags = self.domains.get(&domain);
-            if let Some(domain_tags) = domain_tags {
-                detections.extend(domain_tags.iter());
+            if let Some(ids) = self.domains.get(&domain) {
+                tag_hits.extend(ids.iter().copied());
             }
             // try with subdomains
-            if let Some(domain_tags) = self.detect_subdomains(&domain) {
-                detections.extend(&domain_tags);
+            if let Some(ids) = self.detect_subdomains(&domain) {
+                tag_hits.extend(ids);
             }
         }
 
         if let Ok(url) = Self::normalize_url(url) {
-            let url_tags = self.urls.get(&url);
-            if let Some(url_tags) = url_tags {
-                detections.extend(url_tags.iter());
+            allowed |= self.allow_urls.contains(url.as_str());
+
+            if let Some(ids) = self.urls.get(&url) {
+                tag_hits.extend(ids.iter().copied());
             }
+
+            tag_hits.extend(
+                self.expressions
+                    .matches(url.as_str())
+                    .into_iter()
+                    .map(|idx| self.expression_tags[idx]),
+            );
+        }
+
+        if self.allow_only {
+            return if allowed {
+                None
+            } else {
+                Some([&self.allow_only_label].into_iter().collect())
+            };
+        }
+
+        if allowed {
+            return None;
         }
 
-        if detections.is_empty() {
+        if tag_hits.is_empty() {
             None
         } else {
-            Some(detections)
+            Some(tag_hits.into_iter().map(|id| &self.tags[id as usize]).collect())
+        }
+    }
+}
+
+/// Bump whenever the on-disk layout of [`Cache`] changes, so stale caches are
+/// rejected instead of being misread.
+#[cfg(feature = "serde")]
+const CACHE_FORMAT_VERSION: u32 = 2;
+
+/// Plain-data mirror of [`Blocklist`], used only for (de)serialization: `Url`,
+/// `Rc`, and `RegexSet` don't (de)serialize directly, so URLs are stored as
+/// strings, tag sets as plain `Vec<u32>`, and expressions as their source patterns.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Cache {
+    format_version: u32,
+    tags: Vec<String>,
+    domains: HashMap<String, Vec<u32>>,
+    urls: HashMap<String, Vec<u32>>,
+    expression_patterns: Vec<String>,
+    expression_tags: Vec<u32>,
+    allow: HashSet<String>,
+    allow_urls: HashSet<String>,
+    allow_only: bool,
+    allow_only_label: String,
+    max_subdomain_depth: usize,
+}
+
+#[cfg(feature = "serde")]
+impl Blocklist {
+    /// Serialize this blocklist to `path` in a compact binary format.
+    pub fn save(&self, path: &Path) -> Result<(), Ut1Error> {
+        let cache = Cache {
+            format_version: CACHE_FORMAT_VERSION,
+            tags: self.tags.clone(),
+            domains: self
+                .domains
+                .iter()
+                .map(|(domain, ids)| (domain.clone(), ids.to_vec()))
+                .collect(),
+            urls: self
+                .urls
+                .iter()
+                .map(|(url, ids)| (url.to_string(), ids.to_vec()))
+                .collect(),
+            expression_patterns: self.expressions.patterns().to_vec(),
+            expression_tags: self.expression_tags.clone(),
+            allow: self.allow.clone(),
+            allow_urls: self.allow_urls.clone(),
+            allow_only: self.allow_only,
+            allow_only_label: self.allow_only_label.clone(),
+            max_subdomain_depth: self.max_subdomain_depth,
+        };
+
+        let file = File::create(path)?;
+        bincode::serialize_into(file, &cache)?;
+        Ok(())
+    }
+
+    /// Load a blocklist previously written by [`save`](Self::save).
+    ///
+    /// Returns [`Ut1Error::StaleCache`] if `path` was produced by an older,
+    /// incompatible format version.
+    pub fn load(path: &Path) -> Result<Self, Ut1Error> {
+        use std::io::{Seek, SeekFrom};
+
+        let mut file = File::open(path)?;
+        // `format_version` is the first field: decode it on its own first, so a cache
+        // written with another layout is reported as stale rather than failing to decode
+        let format_version: u32 = bincode::deserialize_from(&mut file)?;
+        if format_version != CACHE_FORMAT_VERSION {
+            return Err(Ut1Error::StaleCache(
+                path.to_path_buf(),
+                format_version,
+                CACHE_FORMAT_VERSION,
+            ));
+        }
+
+        file.seek(SeekFrom::Start(0))?;
+        let cache: Cache = bincode::deserialize_from(file)?;
+
+        let domains = cache
+            .domains
+            .into_iter()
+            .map(|(domain, ids)| (domain, Rc::new(TagIds::from_vec(ids))))
+            .collect();
+        let urls = cache
+            .urls
+            .into_iter()
+            .filter_map(|(url, ids)| Some((url.parse::<Url>().ok()?, Rc::new(TagIds::from_vec(ids)))))
+            .collect();
+        let expressions = RegexSet::new(&cache.expression_patterns).unwrap_or_else(|e| {
+            warn!("failed to recompile cached expression patterns: {e}");
+            RegexSet::empty()
+        });
+
+        Ok(Self {
+            tags: cache.tags,
+            domains,
+            urls,
+            expressions,
+            expression_tags: cache.expression_tags,
+            allow: cache.allow,
+            allow_urls: cache.allow_urls,
+            allow_only: cache.allow_only,
+            allow_only_label: cache.allow_only_label,
+            max_subdomain_depth: cache.max_subdomain_depth,
+        })
+    }
+
+    /// Load `cache_path` if it exists and is at least as recent as `dir`,
+    /// otherwise rebuild from `dir` via [`from_dir`](Self::from_dir) and
+    /// (re)write `cache_path` for next time.
+    pub fn load_or_build(dir: &Path, cache_path: &Path) -> Result<Self, Ut1Error> {
+        let is_fresh = || -> Option<bool> {
+            let cache_mtime = std::fs::metadata(cache_path).ok()?.modified().ok()?;
+            let dir_mtime = std::fs::metadata(dir).ok()?.modified().ok()?;
+            Some(cache_mtime >= dir_mtime)
+        };
+
+        if is_fresh().unwrap_or(false) {
+            if let Ok(blocklist) = Self::load(cache_path) {
+                return Ok(blocklist);
+            }
         }
+
+        let blocklist = Self::from_dir(dir).map_err(Ut1Error::Cache)?;
+        blocklist.save(cache_path)?;
+        Ok(blocklist)
     }
 }
 
@@ -275,10 +628,12 @@ impl Blocklist {
 mod tests {
     use std::{
         collections::{HashMap, HashSet},
-        ops::Deref,
+        fs::File,
+        io::Write,
         path::Path,
     };
 
+    use regex::RegexSet;
     use url::Url;
 
     use super::Blocklist;
@@ -305,8 +660,7 @@ mod tests {
         ]
         .into_iter()
         .collect();
-        // let domains = vec![].into_iter().collect();
-        let b = Blocklist::new(domains, HashMap::new());
+        let b = Blocklist::new(domains, HashMap::new(), RegexSet::empty(), Vec::new());
 
         let test_urls: Vec<(_, Option<HashSet<_>>)> = vec![
             ("https://ujj.blogspot.com/things", Some(vec!["blog"])),
@@ -322,13 +676,7 @@ mod tests {
             ),
         ]
         .into_iter()
-        .map(|(link, cat)| {
-            (
-                link,
-                // cat.map(|x| x.into_iter().map(|y| String::from(y)).collect()),
-                cat.map(|x| x.into_iter().collect()),
-            )
-        })
+        .map(|(link, cat)| (link, cat.map(|x| x.into_iter().collect())))
         .collect();
 
         for (test_url, categories) in test_urls {
@@ -339,6 +687,40 @@ mod tests {
         }
     }
 
+    #[test]
+    fn from_dir_compiles_expressions_and_skips_invalid_patterns() {
+        let root = tempfile::tempdir().unwrap();
+        let category = root.path().join("adult");
+        std::fs::create_dir(&category).unwrap();
+        File::create(category.join("expressions"))
+            .unwrap()
+            .write_all(b"/wp-admin\n[invalid(regex\n")
+            .unwrap();
+
+        let b = Blocklist::from_dir(root.path()).unwrap();
+
+        assert_eq!(
+            b.detect("https://foo.bar/wp-admin/login")
+                .map(|x| x.into_iter().map(|cat| cat.as_str()).collect()),
+            Some(HashSet::from(["adult"]))
+        );
+        assert_eq!(b.detect("https://foo.bar/safe"), None);
+    }
+
+    #[test]
+    fn stats_category_count_excludes_allow_only_label() {
+        let domains = vec![
+            ("foo.bar".to_string(), vec!["adult".to_string()]),
+            ("baz.quux".to_string(), vec!["gambling".to_string()]),
+        ]
+        .into_iter()
+        .collect();
+        let b = Blocklist::new(domains, HashMap::new(), RegexSet::empty(), Vec::new())
+            .allow_only(true);
+
+        assert_eq!(b.stats().category_count, 2);
+    }
+
     // TODO: Check if this test is actually useful?
     #[test]
     fn test_normalize_domain_add_https() {
@@ -352,4 +734,24 @@ mod tests {
         let url = "cri.univ-tlse1.fr/tools/test_filtrage/astrology/";
         let _normalized = Blocklist::normalize_url(url).unwrap();
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn save_and_load_roundtrip() {
+        let domains = vec![("blogspot.com".to_string(), vec!["blog".to_string()])]
+            .into_iter()
+            .collect();
+        let b = Blocklist::new(domains, HashMap::new(), RegexSet::empty(), Vec::new());
+
+        let cache_file = tempfile::NamedTempFile::new().unwrap();
+        b.save(cache_file.path()).unwrap();
+        let loaded = Blocklist::load(cache_file.path()).unwrap();
+
+        assert_eq!(
+            loaded
+                .detect("https://foo.blogspot.com")
+                .map(|x| x.into_iter().map(|cat| cat.as_str()).collect()),
+            Some(HashSet::from(["blog"]))
+        );
+    }
 }
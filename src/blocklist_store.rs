@@ -0,0 +1,187 @@
+/*! Multi-category classification store.
+
+Where [`crate::Blocklist`] answers a single yes/no question for one category,
+[`BlocklistStore`] scans a whole UT1 `blacklists/` root and answers
+"which of these ~100 categories does this URL belong to?", loading each
+category's [`Blocklist`] lazily and caching it for subsequent lookups.
+
+!*/
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use rayon::prelude::*;
+use regex::RegexSet;
+use url::Url;
+
+use crate::{blocklist::Blocklist, error::Ut1Error as Error};
+
+/// Scans a UT1 `blacklists/` root and classifies URLs against every category found there.
+///
+/// Each category's `domains`/`urls` pair is only read from disk the first time it's needed.
+pub struct BlocklistStore {
+    folder: PathBuf,
+    // leaked once at construction: the set of kinds is fixed for the store's
+    // lifetime, so borrowing them as `'static` avoids threading a lifetime
+    // parameter through the store and its cache.
+    kinds: Vec<&'static str>,
+    // a `Mutex`, not a `RefCell`, so the store stays `Sync` and `classify_batch`
+    // can query it from multiple rayon worker threads. Values are `Arc`-wrapped
+    // so `matches` can clone one out and release the lock before running the
+    // (possibly expensive) detection methods, keeping the fan-out parallel.
+    loaded: Mutex<HashMap<&'static str, Arc<Blocklist<'static>>>>,
+}
+
+impl BlocklistStore {
+    /// Scan `folder` and register every subdirectory as a category (`kind`).
+    ///
+    /// Categories are not read from disk until first queried.
+    pub fn with_folder(folder: &Path) -> Result<Self, Error> {
+        if !folder.is_dir() {
+            return Err(Error::NotADirectory(folder.to_path_buf()));
+        }
+
+        let mut kinds = Vec::new();
+        for entry in std::fs::read_dir(folder).map_err(|_| Error::NotADirectory(folder.to_path_buf()))?
+        {
+            let Ok(entry) = entry else { continue };
+            if !entry.path().is_dir() {
+                continue;
+            }
+
+            let kind: &'static str = Box::leak(entry.file_name().to_string_lossy().into_owned().into_boxed_str());
+            kinds.push(kind);
+        }
+
+        Ok(Self {
+            folder: folder.to_path_buf(),
+            kinds,
+            loaded: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Every category name discovered under the store's folder.
+    pub fn kinds(&self) -> &[&'static str] {
+        &self.kinds
+    }
+
+    /// All categories `url` matches (by domain, URL, or expression).
+    pub fn categories(&self, url: &Url) -> Vec<&'static str> {
+        self.kinds
+            .iter()
+            .filter(|&&kind| self.matches(kind, url))
+            .copied()
+            .collect()
+    }
+
+    /// The first category `url` matches, if any. Cheaper than [`categories`](Self::categories)
+    /// when the caller only needs a yes/no-with-reason answer.
+    pub fn first_match(&self, url: &Url) -> Option<&'static str> {
+        self.kinds.iter().find(|&&kind| self.matches(kind, url)).copied()
+    }
+
+    /// Classify a batch of URLs in parallel, returning each URL's matching
+    /// categories in input order.
+    ///
+    /// Prefer this over calling [`categories`](Self::categories) in a loop when
+    /// classifying large streams: categories are loaded from disk at most once
+    /// across the whole batch, and lookups run across a rayon thread pool.
+    pub fn classify_batch(&self, urls: &[Url]) -> Vec<Vec<&'static str>> {
+        urls.par_iter().map(|url| self.categories(url)).collect()
+    }
+
+    /// `true` if `url` matches `kind`'s domain, URL, or expression list, loading
+    /// `kind`'s [`Blocklist`] from disk on first use.
+    fn matches(&self, kind: &'static str, url: &Url) -> bool {
+        // only the lazy-load step needs the lock; clone the `Arc` out and drop
+        // the guard before running detection, so concurrent `classify_batch`
+        // lookups don't serialize on one global mutex.
+        let blocklist = {
+            let mut loaded = self.loaded.lock().unwrap();
+            loaded
+                .entry(kind)
+                .or_insert_with(|| {
+                    Arc::new(Blocklist::with_folder(kind, &self.folder).unwrap_or_else(|e| {
+                        log::warn!("failed to load blocklist {kind:?}, treating it as empty: {e}");
+                        Blocklist::new(kind, HashSet::new(), HashSet::new(), RegexSet::empty())
+                    }))
+                })
+                .clone()
+        };
+
+        blocklist.detect_domain(url) || blocklist.detect_url(url) || blocklist.detect_expression(url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::File, io::Write, str::FromStr};
+
+    use url::Url;
+
+    use super::BlocklistStore;
+
+    fn get_test_store() -> BlocklistStore {
+        let root = tempfile::tempdir().unwrap();
+
+        let adult = root.path().join("adult");
+        std::fs::create_dir(&adult).unwrap();
+        File::create(adult.join("domains"))
+            .unwrap()
+            .write_all("foo.bar".as_bytes())
+            .unwrap();
+
+        let gambling = root.path().join("gambling");
+        std::fs::create_dir(&gambling).unwrap();
+        File::create(gambling.join("domains"))
+            .unwrap()
+            .write_all("baz.quux".as_bytes())
+            .unwrap();
+
+        // store must outlive the tempdir for the test, so leak it like a real
+        // long-lived process would keep its blacklists folder around
+        let path = Box::leak(root.keep().into_boxed_path());
+        BlocklistStore::with_folder(path).unwrap()
+    }
+
+    #[test]
+    fn categories_finds_matching_kind() {
+        let store = get_test_store();
+        let url = Url::from_str("https://foo.bar").unwrap();
+
+        assert_eq!(store.categories(&url), vec!["adult"]);
+    }
+
+    #[test]
+    fn categories_empty_when_no_match() {
+        let store = get_test_store();
+        let url = Url::from_str("https://good.domain").unwrap();
+
+        assert!(store.categories(&url).is_empty());
+    }
+
+    #[test]
+    fn first_match_returns_some_kind() {
+        let store = get_test_store();
+        let url = Url::from_str("https://baz.quux").unwrap();
+
+        assert_eq!(store.first_match(&url), Some("gambling"));
+    }
+
+    #[test]
+    fn classify_batch_matches_categories_in_order() {
+        let store = get_test_store();
+        let urls = vec![
+            Url::from_str("https://foo.bar").unwrap(),
+            Url::from_str("https://good.domain").unwrap(),
+            Url::from_str("https://baz.quux").unwrap(),
+        ];
+
+        assert_eq!(
+            store.classify_batch(&urls),
+            vec![vec!["adult"], vec![], vec!["gambling"]]
+        );
+    }
+}
@@ -0,0 +1,69 @@
+//! Shared public-suffix boundary walk.
+//!
+//! Several modules need to climb from a domain up through its parent domains
+//! without treating a bare ICANN public suffix as a candidate in its own right
+//! (e.g. a `co.uk` entry must never match `evil.co.uk` just because
+//! `evil.co.uk`'s parent happens to be the public suffix `co.uk` — nobody
+//! "owns" `co.uk`, so blocking it generically would be meaningless).
+//!
+//! PSL *private*-section suffixes are the opposite case: UT1's blog/hosting
+//! categories specifically list domains like `blogspot.com` or `github.io`,
+//! which are themselves recognized PSL suffixes but are concrete, block-able
+//! services — `foo.blogspot.com` must still match a bare `blogspot.com` entry.
+//! [`registrable_parents`] centralizes that walk so the boundary check only
+//! has to be right once.
+use psl::Type;
+
+/// Yields `domain`'s parent domains, from the most specific to the least.
+///
+/// Stops strictly above an ICANN (or unrecognized-TLD) public-suffix boundary,
+/// so a bare `co.uk`-style suffix is never yielded as a candidate. A PSL
+/// *private*-section suffix (`blogspot.com`, `github.io`, ...) is yielded even
+/// when a candidate is exactly that suffix, since those are the concrete
+/// entries UT1 categories list.
+pub(crate) fn registrable_parents(domain: &str) -> impl Iterator<Item = &str> {
+    let bytes = domain.as_bytes();
+    let suffix = psl::suffix(bytes);
+    let suffix_len = suffix.as_ref().map(|suffix| suffix.as_bytes().len()).unwrap_or(0);
+    let is_private_suffix = matches!(suffix.as_ref().and_then(|suffix| suffix.typ()), Some(Type::Private));
+
+    bytes
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| c == &&(b'.'))
+        .map(|(idx, _)| idx)
+        .filter(move |&pos| {
+            let candidate_len = domain.len() - (pos + 1);
+            if is_private_suffix {
+                candidate_len >= suffix_len
+            } else {
+                candidate_len > suffix_len
+            }
+        })
+        .map(move |pos| &domain[pos + 1..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::registrable_parents;
+
+    #[test]
+    fn excludes_bare_icann_public_suffix() {
+        let parents: Vec<&str> = registrable_parents("evil.co.uk").collect();
+        assert_eq!(parents, Vec::<&str>::new());
+    }
+
+    #[test]
+    fn includes_parents_above_the_suffix() {
+        let parents: Vec<&str> = registrable_parents("ads.cdn.foo.bar").collect();
+        assert_eq!(parents, vec!["cdn.foo.bar", "foo.bar"]);
+    }
+
+    #[test]
+    fn includes_private_suffix_entries() {
+        // `blogspot.com` is itself a recognized PSL *private*-section suffix,
+        // but UT1 categories list it directly, so it must still be yielded.
+        let parents: Vec<&str> = registrable_parents("ujj.blogspot.com").collect();
+        assert_eq!(parents, vec!["blogspot.com"]);
+    }
+}
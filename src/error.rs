@@ -12,4 +12,15 @@ pub enum Ut1Error {
     MalformedUrl(#[from] ParseError),
     #[error("No host/domain found")]
     NoHostname(String),
+    #[error("failed to build/read FST index at {0}: {1}")]
+    Fst(PathBuf, String),
+    #[error("I/O error building, reading or writing compiled blocklist cache: {0}")]
+    #[cfg(feature = "serde")]
+    Cache(#[from] std::io::Error),
+    #[error("failed to (de)serialize compiled blocklist: {0}")]
+    #[cfg(feature = "serde")]
+    Serialization(#[from] bincode::Error),
+    #[error("cache at {0} was built with format version {1}, expected {2}; rebuild it")]
+    #[cfg(feature = "serde")]
+    StaleCache(PathBuf, u32, u32),
 }
@@ -0,0 +1,135 @@
+/*! Allowlist/denylist composition.
+
+[`FilterSet`] composes one or more [`Blocklist`]s with an optional allowlist,
+so that a URL flagged by a category can be rescued by an explicit allow entry
+(e.g. allow `en.wikipedia.org` even though a broad category lists its parent),
+without editing the upstream UT1 files.
+
+!*/
+use std::collections::HashSet;
+
+use url::Url;
+
+use crate::blocklist::Blocklist;
+
+/// Outcome of [`FilterSet::verdict`].
+///
+/// Precedence is `Allowed` > `Blocked` > `Neutral`: the allowlist always wins,
+/// even over a matching blocklist category.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict<'a> {
+    /// The URL matched the allowlist, regardless of any blocklist hit.
+    Allowed,
+    /// The URL matched `kind`'s domain, URL, or expression list.
+    Blocked {
+        /// The category responsible for the block.
+        kind: &'a str,
+    },
+    /// The URL matched neither the allowlist nor any composed blocklist.
+    Neutral,
+}
+
+/// Composes one or more [`Blocklist`]s with an optional allowlist.
+pub struct FilterSet<'a> {
+    blocklists: Vec<Blocklist<'a>>,
+    allow: HashSet<String>,
+    allow_urls: HashSet<String>,
+}
+
+impl<'a> FilterSet<'a> {
+    /// Compose `blocklists`, queried in order; the first to match wins.
+    pub fn new(blocklists: Vec<Blocklist<'a>>) -> Self {
+        Self {
+            blocklists,
+            allow: HashSet::new(),
+            allow_urls: HashSet::new(),
+        }
+    }
+
+    /// Add domains/URLs that must always resolve to [`Verdict::Allowed`],
+    /// overriding every composed blocklist.
+    pub fn with_allowlist(
+        mut self,
+        domains: impl IntoIterator<Item = String>,
+        urls: impl IntoIterator<Item = String>,
+    ) -> Self {
+        self.allow = domains.into_iter().collect();
+        self.allow_urls = urls.into_iter().collect();
+        self
+    }
+
+    /// Classify `url` against the allowlist first, then each composed blocklist in order.
+    pub fn verdict(&self, url: &Url) -> Verdict<'a> {
+        if self.is_allowed(url) {
+            return Verdict::Allowed;
+        }
+
+        self.blocklists
+            .iter()
+            .find(|blocklist| {
+                blocklist.detect_domain(url) || blocklist.detect_url(url) || blocklist.detect_expression(url)
+            })
+            .map(|blocklist| Verdict::Blocked { kind: blocklist.kind() })
+            .unwrap_or(Verdict::Neutral)
+    }
+
+    fn is_allowed(&self, url: &Url) -> bool {
+        let url_matches = url[url::Position::BeforeHost..url::Position::AfterPath].to_string();
+        if self.allow_urls.contains(&url_matches) {
+            return true;
+        }
+
+        let Some(domain) = url.host_str() else {
+            return false;
+        };
+        if self.allow.contains(domain) {
+            return true;
+        }
+
+        crate::domain_suffix::registrable_parents(domain).any(|parent| self.allow.contains(parent))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, str::FromStr};
+
+    use regex::RegexSet;
+    use url::Url;
+
+    use super::{FilterSet, Verdict};
+    use crate::blocklist::Blocklist;
+
+    #[test]
+    fn blocked_when_no_allowlist() {
+        let domains = HashSet::from(["encyclopedia.example".to_string()]);
+        let blocklist = Blocklist::new("reference", domains, HashSet::new(), RegexSet::empty());
+        let filter = FilterSet::new(vec![blocklist]);
+
+        let url = Url::from_str("https://en.encyclopedia.example").unwrap();
+        assert_eq!(filter.verdict(&url), Verdict::Neutral);
+
+        let url = Url::from_str("https://encyclopedia.example").unwrap();
+        assert_eq!(filter.verdict(&url), Verdict::Blocked { kind: "reference" });
+    }
+
+    #[test]
+    fn allowlist_overrides_blocklist() {
+        let domains = HashSet::from(["encyclopedia.example".to_string()]);
+        let blocklist = Blocklist::new("reference", domains, HashSet::new(), RegexSet::empty());
+        let filter = FilterSet::new(vec![blocklist])
+            .with_allowlist(["encyclopedia.example".to_string()], []);
+
+        let url = Url::from_str("https://encyclopedia.example").unwrap();
+        assert_eq!(filter.verdict(&url), Verdict::Allowed);
+    }
+
+    #[test]
+    fn neutral_with_no_match() {
+        let blocklist = Blocklist::new("reference", HashSet::new(), HashSet::new(), RegexSet::empty());
+        let filter = FilterSet::new(vec![blocklist]);
+
+        let url = Url::from_str("https://good.domain").unwrap();
+        assert_eq!(filter.verdict(&url), Verdict::Neutral);
+    }
+}
@@ -2,8 +2,13 @@
 
 pub mod blocklist;
 pub mod blocklist_multi;
+pub mod blocklist_store;
+mod domain_suffix;
 mod error;
+pub mod filter_set;
 
 pub use blocklist::Blocklist;
 pub use blocklist_multi::Blocklist as MultipleBlocklist;
+pub use blocklist_store::BlocklistStore;
 pub use error::Ut1Error as Error;
+pub use filter_set::{FilterSet, Verdict};